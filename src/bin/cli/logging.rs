@@ -0,0 +1,107 @@
+//! Logging backends for the CLI: a greppable (uncolored) file sink, a
+//! colored terminal sink, and an optional syslog sink for boot/init flows.
+//! Requires a `syslog = ["dep:syslog"]` feature in `Cargo.toml`.
+
+use std::fs::OpenOptions;
+
+use clap::ValueEnum;
+use simplelog::{
+    CombinedLogger, Config, ConfigBuilder, LevelFilter, SharedLogger, TermLogger, TerminalMode,
+    WriteLogger,
+};
+
+use crate::CliError;
+
+/// Verbosity levels accepted by `--log-level`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+#[value(rename_all = "lower")]
+pub(crate) enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Configure logging for the CLI.
+///
+/// # Arguments
+/// * `enable_terminal` - If true, also logs to the terminal in addition to the file
+/// * `enable_syslog` - If true, logs to syslog instead of the file/terminal sinks
+/// * `log_file` - Path of the file to append log records to
+/// * `level` - Verbosity applied to every enabled sink
+///
+/// # Returns
+/// * `Ok(())` - If logging was initialized successfully
+/// * `Err(CliError)` - If an error occurred during initialization, or syslog
+///   was requested in a build without the `syslog` feature
+pub(crate) fn init(
+    enable_terminal: bool,
+    enable_syslog: bool,
+    log_file: &str,
+    level: LogLevel,
+) -> Result<(), CliError> {
+    let level = LevelFilter::from(level);
+
+    // `log` only supports a single global logger, so syslog replaces the
+    // file/terminal sinks rather than joining them.
+    if enable_syslog {
+        return init_syslog(level);
+    }
+
+    let mut loggers: Vec<Box<dyn SharedLogger>> = vec![create_file_logger(log_file, level)?];
+
+    if enable_terminal {
+        loggers.push(create_terminal_logger(level));
+    }
+
+    CombinedLogger::init(loggers).map_err(CliError::LogError)
+}
+
+#[cfg(feature = "syslog")]
+fn init_syslog(level: LevelFilter) -> Result<(), CliError> {
+    syslog::init(syslog::Facility::LOG_DAEMON, level, Some("regmsg")).map_err(|e| {
+        CliError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+    })
+}
+
+#[cfg(not(feature = "syslog"))]
+fn init_syslog(_level: LevelFilter) -> Result<(), CliError> {
+    Err(CliError::IoError(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "regmsg was built without the `syslog` feature",
+    )))
+}
+
+/// Creates a file logger. The file sink never emits ANSI color codes, so
+/// `/var/log/regmsg.log` stays greppable.
+fn create_file_logger(log_file: &str, level: LevelFilter) -> Result<Box<dyn SharedLogger>, CliError> {
+    let file = OpenOptions::new().create(true).append(true).open(log_file)?;
+    let config = ConfigBuilder::new().set_write_log_enable_colors(false).build();
+
+    Ok(WriteLogger::new(level, config, file))
+}
+
+/// Creates a terminal logger, colored when the terminal supports it.
+fn create_terminal_logger(level: LevelFilter) -> Box<dyn SharedLogger> {
+    TermLogger::new(
+        level,
+        Config::default(),
+        TerminalMode::Mixed,
+        simplelog::ColorChoice::Auto,
+    )
+}
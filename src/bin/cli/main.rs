@@ -1,25 +1,33 @@
 #![cfg(feature = "cli")]
 
+mod logging;
+mod record;
+
 use clap::{Parser, Subcommand};
-use simplelog::{CombinedLogger, Config, LevelFilter, TermLogger, TerminalMode, WriteLogger};
+use serde::Serialize;
 use std::fmt;
-use std::fs::OpenOptions;
+use std::time::Duration;
 
 use zeromq::ReqSocket; // or DealerSocket, RouterSocket, etc.
+use zeromq::SubSocket;
 use zeromq::ZmqMessage;
 use zeromq::prelude::*; // traits
 
-// Constants for paths and configuration
-const DAEMON_SOCKET_PATH: &str = "ipc:///var/run/regmsgd.sock";
-const LOG_FILE_PATH: &str = "/var/log/regmsg.log";
+// Default paths, overridable via CLI flags or environment variables
+const DEFAULT_DAEMON_SOCKET_PATH: &str = "ipc:///var/run/regmsgd.sock";
+const DEFAULT_DAEMON_PUBLISH_PATH: &str = "ipc:///var/run/regmsgd-events.sock";
+const DEFAULT_LOG_FILE_PATH: &str = "/var/log/regmsg.log";
 
 /// Custom error type for CLI operations
 #[derive(Debug)]
-enum CliError {
+pub(crate) enum CliError {
     SocketError(zeromq::ZmqError),
     Utf8Error(std::string::FromUtf8Error),
     IoError(std::io::Error),
     LogError(log::SetLoggerError),
+    JsonError(serde_json::Error),
+    Blacklisted(String),
+    Timeout(u64),
 }
 
 impl fmt::Display for CliError {
@@ -29,6 +37,13 @@ impl fmt::Display for CliError {
             CliError::Utf8Error(e) => write!(f, "UTF-8 error: {}", e),
             CliError::IoError(e) => write!(f, "IO error: {}", e),
             CliError::LogError(e) => write!(f, "Log error: {}", e),
+            CliError::JsonError(e) => write!(f, "JSON error: {}", e),
+            CliError::Blacklisted(id) => write!(f, "{} is blacklisted from recording", id),
+            CliError::Timeout(ms) => write!(
+                f,
+                "daemon did not respond within {}ms (is regmsgd running?)",
+                ms
+            ),
         }
     }
 }
@@ -59,18 +74,233 @@ impl From<log::SetLoggerError> for CliError {
     }
 }
 
+impl From<serde_json::Error> for CliError {
+    fn from(error: serde_json::Error) -> Self {
+        CliError::JsonError(error)
+    }
+}
+
+/// A display mode, as reported by `listModes`/`currentMode` (`WxH@R`).
+#[derive(Debug, Serialize)]
+struct Mode {
+    width: u32,
+    height: u32,
+    refresh: u32,
+}
+
+/// A display output, as reported by `listOutputs`.
+#[derive(Debug, Serialize)]
+struct Output {
+    name: String,
+    connected: bool,
+    current: bool,
+}
+
+/// A display resolution, as reported by `currentResolution` (`WxH`).
+#[derive(Debug, Serialize, PartialEq)]
+pub(crate) struct Resolution {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+/// A scalar daemon reply that doesn't warrant its own struct.
+#[derive(Debug, Serialize)]
+struct ValueReply {
+    value: String,
+}
+
+/// A live display-change event received from the daemon's publish endpoint.
+///
+/// Each variant carries the same typed payload used by `--json` command
+/// replies, so `watch` and one-shot commands stay consistent.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", content = "data")]
+enum DisplayEvent {
+    OutputAdded(Output),
+    OutputRemoved(Output),
+    ModeChanged(Mode),
+    RotationChanged(ValueReply),
+}
+
+impl fmt::Display for DisplayEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayEvent::OutputAdded(o) => write!(f, "output added: {}", o.name),
+            DisplayEvent::OutputRemoved(o) => write!(f, "output removed: {}", o.name),
+            DisplayEvent::ModeChanged(m) => {
+                write!(f, "mode changed: {}x{}@{}", m.width, m.height, m.refresh)
+            }
+            DisplayEvent::RotationChanged(v) => write!(f, "rotation changed: {}", v.value),
+        }
+    }
+}
+
+/// Decode one multipart publish message (`topic` frame + `payload` frame)
+/// into a [`DisplayEvent`].
+pub(crate) fn parse_event(topic: &str, payload: &str) -> Option<DisplayEvent> {
+    match topic {
+        "outputAdded" => parse_outputs(payload).into_iter().next().map(DisplayEvent::OutputAdded),
+        "outputRemoved" => parse_outputs(payload)
+            .into_iter()
+            .next()
+            .map(DisplayEvent::OutputRemoved),
+        "modeChanged" => parse_mode(payload).map(DisplayEvent::ModeChanged),
+        "rotationChanged" => Some(DisplayEvent::RotationChanged(ValueReply {
+            value: payload.trim().to_string(),
+        })),
+        _ => None,
+    }
+}
+
+/// Connect to the daemon's publish endpoint and print one line per display
+/// event until interrupted.
+///
+/// # Arguments
+/// * `cli` - The parsed command line arguments
+///
+/// # Returns
+/// * `Ok(())` - Never; the event loop only ends with an error
+/// * `Err(CliError)` - If the publish socket fails, or an event frame is not
+///   UTF-8 or cannot be serialized to JSON
+async fn run_watch(cli: &Cli) -> Result<(), CliError> {
+    let mut socket = SubSocket::new();
+    socket.connect(&cli.publish).await?;
+    socket.subscribe("").await?;
+
+    loop {
+        let message = socket.recv().await?;
+        let topic = match message.get(0) {
+            Some(frame) => String::from_utf8(frame.to_vec())?,
+            None => continue,
+        };
+        let payload = match message.get(1) {
+            Some(frame) => String::from_utf8(frame.to_vec())?,
+            None => String::new(),
+        };
+
+        let Some(event) = parse_event(&topic, &payload) else {
+            continue;
+        };
+
+        if cli.json {
+            println!("{}", serde_json::to_string(&event)?);
+        } else {
+            println!("{}", event);
+        }
+    }
+}
+
+/// Parse a single `WxH@R` mode line (used for both `currentMode` and each
+/// line of `listModes`).
+fn parse_mode(line: &str) -> Option<Mode> {
+    let (res, refresh) = line.trim().split_once('@')?;
+    let (width, height) = res.split_once('x')?;
+    Some(Mode {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        refresh: refresh.parse().ok()?,
+    })
+}
+
+/// Parse the newline-separated `WxH@R` reply of `listModes`.
+fn parse_modes(reply: &str) -> Vec<Mode> {
+    reply.lines().filter_map(parse_mode).collect()
+}
+
+/// Parse the `WxH` reply of `currentResolution`.
+pub(crate) fn parse_resolution(reply: &str) -> Option<Resolution> {
+    let (width, height) = reply.trim().split_once('x')?;
+    Some(Resolution {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+    })
+}
+
+/// Parse the newline-separated `name connected current` reply of `listOutputs`,
+/// where `connected`/`current` are `0`/`1`.
+fn parse_outputs(reply: &str) -> Vec<Output> {
+    reply
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(Output {
+                name: fields.next()?.to_string(),
+                connected: fields.next()? == "1",
+                current: fields.next()? == "1",
+            })
+        })
+        .collect()
+}
+
+/// Convert a command's raw textual daemon reply into its typed JSON
+/// representation.
+///
+/// Commands with a structured reply (`listModes`, `listOutputs`,
+/// `currentMode`, `currentResolution`) are parsed into their matching
+/// struct; everything else is treated as a scalar and wrapped in
+/// `{"value": ...}`.
+fn reply_to_json(command: &Commands, reply: &str) -> Result<String, serde_json::Error> {
+    match command {
+        Commands::ListModes => serde_json::to_string(&parse_modes(reply)),
+        Commands::ListOutputs => serde_json::to_string(&parse_outputs(reply)),
+        Commands::CurrentMode => match parse_mode(reply) {
+            Some(mode) => serde_json::to_string(&mode),
+            None => serde_json::to_string(&ValueReply {
+                value: reply.trim().to_string(),
+            }),
+        },
+        Commands::CurrentResolution => match parse_resolution(reply) {
+            Some(resolution) => serde_json::to_string(&resolution),
+            None => serde_json::to_string(&ValueReply {
+                value: reply.trim().to_string(),
+            }),
+        },
+        _ => serde_json::to_string(&ValueReply {
+            value: reply.trim().to_string(),
+        }),
+    }
+}
+
 /// Global CLI arguments
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
-struct Cli {
+pub(crate) struct Cli {
     /// Target screen identifier (optional)
     #[arg(short, long)]
-    screen: Option<String>,
+    pub(crate) screen: Option<String>,
 
     /// Enable terminal logging
     #[arg(short, long)]
     log: bool,
 
+    /// Emit machine-readable JSON instead of the daemon's raw text reply
+    #[arg(long)]
+    pub(crate) json: bool,
+
+    /// Address of the regmsgd daemon socket
+    #[arg(long, env = "REGMSGD_SOCKET", default_value = DEFAULT_DAEMON_SOCKET_PATH)]
+    pub(crate) server: String,
+
+    /// Path of the log file records are appended to (not used with `--syslog`)
+    #[arg(long = "log-file", env = "REGMSG_LOG", default_value = DEFAULT_LOG_FILE_PATH)]
+    log_file: String,
+
+    /// Address of the regmsgd event publish endpoint (used by `watch`)
+    #[arg(long, env = "REGMSGD_PUB", default_value = DEFAULT_DAEMON_PUBLISH_PATH)]
+    pub(crate) publish: String,
+
+    /// Verbosity applied to every enabled log sink
+    #[arg(long = "log-level", value_enum, default_value = "info")]
+    log_level: logging::LogLevel,
+
+    /// Log to syslog instead of the log file/terminal (requires the `syslog` feature)
+    #[arg(long)]
+    syslog: bool,
+
+    /// Milliseconds to wait for the daemon to reply before giving up
+    #[arg(long, default_value_t = 5000)]
+    pub(crate) timeout: u64,
+
     /// Subcommand to execute
     #[command(subcommand)]
     command: Commands,
@@ -83,7 +313,7 @@ struct Cli {
 /// List of available subcommands
 #[derive(Subcommand, Debug)]
 #[command(rename_all = "camelCase")] // <--- all variants become camelCase
-enum Commands {
+pub(crate) enum Commands {
     #[command(about = "Lists all available outputs (e.g., HDMI, VGA).")]
     ListModes,
     #[command(about = "List all available display outputs")]
@@ -117,56 +347,42 @@ enum Commands {
         about = "Sets the screen resolution to the maximum supported resolution (e.g., 1920x1080)."
     )]
     MinToMaxResolution,
+    #[command(
+        about = "Runs a ';'-separated list of daemon commands over a single connection (e.g. \"setOutput 1920x1080@60 ; setRotation 90\")."
+    )]
+    Sequence { script: String },
+    #[command(
+        about = "Streams live display events (output hotplug, mode/rotation changes) from the daemon until interrupted."
+    )]
+    Watch,
+    #[command(
+        about = "Records the active output to a file via wf-recorder, following it across output switches."
+    )]
+    RecordScreen {
+        output_file: String,
+        /// Never record while this output is active
+        #[arg(long = "not-output", value_name = "OUTPUT")]
+        not_output: Vec<String>,
+        /// Never record while this screen is targeted
+        #[arg(long = "not-screen", value_name = "SCREEN")]
+        not_screen: Vec<String>,
+    },
+    #[command(
+        about = "Streams the active output to an RTMP sink via wf-recorder/ffmpeg, following it across output switches."
+    )]
+    StreamScreen {
+        rtmp_url: String,
+        /// Never stream while this output is active
+        #[arg(long = "not-output", value_name = "OUTPUT")]
+        not_output: Vec<String>,
+        /// Never stream while this screen is targeted
+        #[arg(long = "not-screen", value_name = "SCREEN")]
+        not_screen: Vec<String>,
+    },
 }
 
-/// Configure file and terminal logging
-///
-/// # Arguments
-/// * `enable_terminal` - If true, enables terminal logging in addition to file logging
-///
-/// # Returns
-/// * `Ok(())` - If logging was initialized successfully
-/// * `Err(CliError)` - If an error occurred during initialization
-fn init_logging(enable_terminal: bool) -> Result<(), CliError> {
-    let mut loggers: Vec<Box<dyn simplelog::SharedLogger>> = vec![create_file_logger()?];
-
-    if enable_terminal {
-        loggers.push(create_terminal_logger());
-    }
-
-    CombinedLogger::init(loggers).map_err(CliError::LogError)
-}
-
-/// Creates a file logger
-///
-/// # Returns
-/// * `Ok(Box<dyn simplelog::SharedLogger>)` - A file logger ready to be used
-/// * `Err(CliError)` - If an error occurred while opening the file
-fn create_file_logger() -> Result<Box<dyn simplelog::SharedLogger>, CliError> {
-    let file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(LOG_FILE_PATH)?;
-
-    Ok(WriteLogger::new(
-        LevelFilter::Debug,
-        Config::default(),
-        file,
-    ))
-}
-
-/// Creates a terminal logger
-///
-/// # Returns
-/// * `Box<dyn simplelog::SharedLogger>` - A terminal logger ready to be used
-fn create_terminal_logger() -> Box<dyn simplelog::SharedLogger> {
-    TermLogger::new(
-        LevelFilter::Info,
-        Config::default(),
-        TerminalMode::Mixed,
-        simplelog::ColorChoice::Auto,
-    )
-}
+/// Separator between individual commands in a `Sequence` script.
+const SEQUENCE_SEPARATOR: char = ';';
 
 /// Main entry point of the CLI application
 ///
@@ -181,12 +397,57 @@ async fn main() -> Result<(), CliError> {
     let cli = Cli::parse();
 
     // Init logging
-    init_logging(cli.log)?;
+    logging::init(cli.log, cli.syslog, &cli.log_file, cli.log_level)?;
+
+    // `watch`, `recordScreen` and `streamScreen` hold a long-lived connection
+    // of their own instead of issuing a single request/reply round-trip.
+    match &cli.command {
+        Commands::Watch => {
+            if let Err(e) = run_watch(&cli).await {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Commands::RecordScreen {
+            output_file,
+            not_output,
+            not_screen,
+        } => {
+            let target = record::RecordTarget::File(output_file.clone());
+            if let Err(e) = record::run(&cli, target, not_output.clone(), not_screen.clone()).await
+            {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Commands::StreamScreen {
+            rtmp_url,
+            not_output,
+            not_screen,
+        } => {
+            let target = record::RecordTarget::Rtmp(rtmp_url.clone());
+            if let Err(e) = record::run(&cli, target, not_output.clone(), not_screen.clone()).await
+            {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
 
     // Connect to daemon via ZeroMQ
     //let ctx = zmq::Context::new();
     let mut socket = ReqSocket::new();
-    let _ = socket.connect(DAEMON_SOCKET_PATH).await;
+    if let Err(e) = socket.connect(&cli.server).await {
+        eprintln!("Error: {e}");
+        if let Some(hint) = missing_socket_hint(&cli.server) {
+            eprintln!("{hint}");
+        }
+        std::process::exit(1);
+    }
 
     // Execute the command
     if let Err(e) = handle_command(&cli, socket).await {
@@ -207,14 +468,18 @@ async fn main() -> Result<(), CliError> {
 /// * `Ok(())` - If the command was executed successfully
 /// * `Err(CliError)` - If an error occurred during execution
 async fn handle_command(cli: &Cli, mut socket: zeromq::ReqSocket) -> Result<(), CliError> {
+    if let Commands::Sequence { script } = &cli.command {
+        return handle_sequence(cli, script, socket).await;
+    }
+
     // Build the complete command
     let cmd = build_command_string(cli);
 
     // Send the command to the daemon
-    let _ = socket.send(ZmqMessage::from(cmd)).await;
+    socket.send(ZmqMessage::from(cmd)).await?;
 
     // Receive and display
-    let reply = socket.recv().await?;
+    let reply = recv_with_timeout(&mut socket, cli.timeout).await?;
 
     // Get the first frame as a UTF-8 string
     let reply_str = match reply.get(0) {
@@ -222,7 +487,11 @@ async fn handle_command(cli: &Cli, mut socket: zeromq::ReqSocket) -> Result<(),
         None => String::new(),
     };
 
-    println!("{}", reply_str); // prints the raw string
+    if cli.json {
+        println!("{}", reply_to_json(&cli.command, &reply_str)?);
+    } else {
+        println!("{}", reply_str); // prints the raw string
+    }
 
     Ok(())
 }
@@ -250,12 +519,17 @@ fn build_command_string(cli: &Cli) -> String {
         Commands::GetScreenshot => "getScreenshot".to_string(),
         Commands::MapTouchScreen => "mapTouchScreen".to_string(),
         Commands::MinToMaxResolution => "minToMaxResolution".to_string(),
+        Commands::Sequence { script } => script.clone(),
+        Commands::Watch => unreachable!("Commands::Watch is handled directly in main"),
+        Commands::RecordScreen { .. } => {
+            unreachable!("Commands::RecordScreen is handled directly in main")
+        }
+        Commands::StreamScreen { .. } => {
+            unreachable!("Commands::StreamScreen is handled directly in main")
+        }
     };
 
-    // Add --screen if specified
-    if let Some(screen) = &cli.screen {
-        cmd.push_str(&format!(" --screen {}", screen));
-    }
+    append_screen(&mut cmd, cli);
 
     // Add additional arguments
     if !cli.args.is_empty() {
@@ -265,3 +539,154 @@ fn build_command_string(cli: &Cli) -> String {
 
     cmd
 }
+
+/// Append ` --screen <id>` to a command string if `--screen` was given.
+fn append_screen(cmd: &mut String, cli: &Cli) {
+    if let Some(screen) = &cli.screen {
+        cmd.push_str(&format!(" --screen {}", screen));
+    }
+}
+
+/// For an `ipc://` socket address whose path doesn't exist on disk, return a
+/// hint that the daemon is probably not running.
+fn missing_socket_hint(server: &str) -> Option<String> {
+    let path = server.strip_prefix("ipc://")?;
+    if std::path::Path::new(path).exists() {
+        None
+    } else {
+        Some(format!(
+            "hint: {} does not exist -- is regmsgd running?",
+            path
+        ))
+    }
+}
+
+/// Receive the daemon's reply, giving up after `timeout_ms`.
+pub(crate) async fn recv_with_timeout(
+    socket: &mut ReqSocket,
+    timeout_ms: u64,
+) -> Result<ZmqMessage, CliError> {
+    match async_std::future::timeout(Duration::from_millis(timeout_ms), socket.recv()).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(CliError::Timeout(timeout_ms)),
+    }
+}
+
+/// Execute each `;`-separated step of a [`Commands::Sequence`] script over
+/// the same connection, in order, aborting as soon as a step's send/recv
+/// round-trip itself fails (socket error or timeout).
+///
+/// This repo has no vendored daemon source and `regmsgd` doesn't document an
+/// in-band error marker in its text replies, so a step's *content* can't be
+/// reliably checked for failure here; only do that once such a convention is
+/// confirmed against the daemon.
+///
+/// # Arguments
+/// * `cli` - The parsed command line arguments
+/// * `script` - The raw `;`-separated list of daemon commands
+/// * `socket` - The ZeroMQ socket to communicate with the daemon
+///
+/// # Returns
+/// * `Ok(())` - If every step's round-trip completed
+/// * `Err(CliError)` - If a step's send/recv failed (socket error or timeout)
+async fn handle_sequence(
+    cli: &Cli,
+    script: &str,
+    mut socket: zeromq::ReqSocket,
+) -> Result<(), CliError> {
+    let steps = script
+        .split(SEQUENCE_SEPARATOR)
+        .map(str::trim)
+        .filter(|step| !step.is_empty());
+
+    for step in steps {
+        let mut cmd = step.to_string();
+        append_screen(&mut cmd, cli);
+
+        socket.send(ZmqMessage::from(cmd)).await?;
+        let reply = recv_with_timeout(&mut socket, cli.timeout).await?;
+        let reply_str = match reply.get(0) {
+            Some(frame) => String::from_utf8(frame.to_vec())?,
+            None => String::new(),
+        };
+
+        if cli.json {
+            let value = ValueReply {
+                value: reply_str.trim().to_string(),
+            };
+            println!("{}", serde_json::to_string(&value)?);
+        } else {
+            println!("{}", reply_str);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mode_accepts_well_formed_line() {
+        let mode = parse_mode("1920x1080@60").expect("should parse");
+        assert_eq!(mode.width, 1920);
+        assert_eq!(mode.height, 1080);
+        assert_eq!(mode.refresh, 60);
+    }
+
+    #[test]
+    fn parse_mode_rejects_malformed_line() {
+        assert!(parse_mode("1920x1080").is_none()); // missing refresh
+        assert!(parse_mode("not a mode").is_none());
+        assert!(parse_mode("").is_none());
+    }
+
+    #[test]
+    fn parse_modes_skips_malformed_lines() {
+        let modes = parse_modes("1920x1080@60\nbogus\n1280x720@30");
+        assert_eq!(modes.len(), 2);
+        assert_eq!(modes[0].refresh, 60);
+        assert_eq!(modes[1].refresh, 30);
+    }
+
+    #[test]
+    fn parse_resolution_accepts_well_formed_reply() {
+        let resolution = parse_resolution("1920x1080").expect("should parse");
+        assert_eq!(resolution, Resolution {
+            width: 1920,
+            height: 1080,
+        });
+    }
+
+    #[test]
+    fn parse_resolution_rejects_malformed_reply() {
+        assert!(parse_resolution("1920").is_none()); // no separator
+        assert!(parse_resolution("1920x").is_none()); // missing height
+        assert!(parse_resolution("").is_none());
+    }
+
+    #[test]
+    fn parse_outputs_skips_malformed_lines() {
+        let outputs = parse_outputs("HDMI-1 1 1\nincomplete\nVGA-1 0 0");
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].name, "HDMI-1");
+        assert!(outputs[0].connected);
+        assert!(outputs[0].current);
+        assert_eq!(outputs[1].name, "VGA-1");
+        assert!(!outputs[1].connected);
+        assert!(!outputs[1].current);
+    }
+
+    #[test]
+    fn reply_to_json_falls_back_to_value_on_malformed_structured_reply() {
+        let json = reply_to_json(&Commands::CurrentResolution, "not a resolution").unwrap();
+        assert_eq!(json, r#"{"value":"not a resolution"}"#);
+    }
+
+    #[test]
+    fn reply_to_json_wraps_scalar_replies() {
+        let json = reply_to_json(&Commands::CurrentOutput, "HDMI-1\n").unwrap();
+        assert_eq!(json, r#"{"value":"HDMI-1"}"#);
+    }
+}
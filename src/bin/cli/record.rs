@@ -0,0 +1,212 @@
+//! `recordScreen`/`streamScreen` support: drive `wf-recorder` (optionally
+//! piped into `ffmpeg` for RTMP) and keep it bound to whichever output is
+//! currently active by watching the daemon's event stream.
+
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use zeromq::prelude::*;
+use zeromq::{ReqSocket, SubSocket, ZmqMessage};
+
+use crate::{Cli, CliError, Resolution, parse_event, parse_resolution, recv_with_timeout};
+
+/// Where a recording should end up.
+pub(crate) enum RecordTarget {
+    /// Passed straight to `wf-recorder -f <path>`.
+    File(String),
+    /// Piped through `ffmpeg` to an RTMP URL.
+    Rtmp(String),
+}
+
+/// The running `wf-recorder` process and, for `Rtmp` targets, the `ffmpeg`
+/// process it's piped into.
+struct Recorder {
+    wf_recorder: Child,
+    sink: Option<Child>,
+}
+
+impl Recorder {
+    /// Spawn `wf-recorder` bound to `output` via `-o`, capturing the whole
+    /// output. No `-g` geometry is passed: a hardcoded `+0+0` origin would
+    /// crop/misplace the capture on any layout where `output` isn't
+    /// positioned at the compositor's global origin, and the daemon doesn't
+    /// expose output position to compute the real one.
+    fn spawn(output: &str, target: &RecordTarget) -> std::io::Result<Self> {
+        match target {
+            RecordTarget::File(path) => {
+                let wf_recorder = Command::new("wf-recorder")
+                    .args(["-o", output, "-f", path])
+                    .spawn()?;
+                Ok(Self {
+                    wf_recorder,
+                    sink: None,
+                })
+            }
+            RecordTarget::Rtmp(url) => {
+                let mut wf_recorder = Command::new("wf-recorder")
+                    .args(["-o", output, "-m", "matroska", "-f", "-"])
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+                let stdout = wf_recorder
+                    .stdout
+                    .take()
+                    .expect("wf-recorder stdout was piped");
+                let sink = Command::new("ffmpeg")
+                    .args(["-i", "-", "-c", "copy", "-f", "flv", url])
+                    .stdin(stdout)
+                    .spawn()?;
+                Ok(Self {
+                    wf_recorder,
+                    sink: Some(sink),
+                })
+            }
+        }
+    }
+
+    fn kill(&mut self) {
+        let _ = self.wf_recorder.kill();
+        let _ = self.wf_recorder.wait();
+        if let Some(sink) = &mut self.sink {
+            let _ = sink.kill();
+            let _ = sink.wait();
+        }
+    }
+}
+
+/// Ask the daemon for the active output and its resolution, over a
+/// short-lived connection, giving up after `timeout_ms` per reply.
+///
+/// `screen` is appended to both requests the same way `append_screen` does
+/// for the other command paths, so a multi-screen daemon is queried about
+/// the screen this invocation actually targets.
+async fn query_current(
+    server: &str,
+    timeout_ms: u64,
+    screen: Option<&str>,
+) -> Result<(String, Resolution), CliError> {
+    let mut socket = ReqSocket::new();
+    socket.connect(server).await?;
+
+    socket
+        .send(ZmqMessage::from(with_screen("currentOutput", screen)))
+        .await?;
+    let output = recv_frame(&mut socket, timeout_ms).await?;
+
+    socket
+        .send(ZmqMessage::from(with_screen("currentResolution", screen)))
+        .await?;
+    let resolution_reply = recv_frame(&mut socket, timeout_ms).await?;
+    let resolution = parse_resolution(&resolution_reply).ok_or_else(|| {
+        CliError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "daemon returned a malformed resolution",
+        ))
+    })?;
+
+    Ok((output.trim().to_string(), resolution))
+}
+
+/// Append ` --screen <id>` to a command string, mirroring `append_screen`.
+fn with_screen(cmd: &str, screen: Option<&str>) -> String {
+    match screen {
+        Some(screen) => format!("{cmd} --screen {screen}"),
+        None => cmd.to_string(),
+    }
+}
+
+async fn recv_frame(socket: &mut ReqSocket, timeout_ms: u64) -> Result<String, CliError> {
+    let reply = recv_with_timeout(socket, timeout_ms).await?;
+    match reply.get(0) {
+        Some(frame) => Ok(String::from_utf8(frame.to_vec())?),
+        None => Ok(String::new()),
+    }
+}
+
+/// Drive a `wf-recorder`/`ffmpeg` child, re-spawning it against whichever
+/// output is active whenever the daemon reports a display change, and
+/// suppressing recording entirely while the active output/screen is
+/// blacklisted.
+///
+/// # Arguments
+/// * `cli` - The parsed command line arguments
+/// * `target` - Where the recording/stream should be written
+/// * `not_output` - Output names that must never be recorded
+/// * `not_screen` - `--screen` identifiers that must never be recorded
+///
+/// # Returns
+/// * `Ok(())` - Never; the event loop only ends with an error
+/// * `Err(CliError)` - `Blacklisted` at once if `--screen` is in `not_screen`;
+///   otherwise if the daemon or recorder could not be reached/spawned
+pub(crate) async fn run(
+    cli: &Cli,
+    target: RecordTarget,
+    not_output: Vec<String>,
+    not_screen: Vec<String>,
+) -> Result<(), CliError> {
+    if let Some(screen) = &cli.screen {
+        if not_screen.iter().any(|s| s == screen) {
+            return Err(CliError::Blacklisted(screen.clone()));
+        }
+    }
+
+    let (mut output, mut resolution) =
+        query_current(&cli.server, cli.timeout, cli.screen.as_deref()).await?;
+    let recorder: Arc<Mutex<Option<Recorder>>> = Arc::new(Mutex::new(None));
+
+    let sigint_recorder = Arc::clone(&recorder);
+    ctrlc::set_handler(move || {
+        if let Ok(mut guard) = sigint_recorder.lock() {
+            if let Some(mut recorder) = guard.take() {
+                recorder.kill();
+            }
+        }
+        std::process::exit(0);
+    })
+    .map_err(|e| CliError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+
+    if not_output.iter().any(|o| o == &output) {
+        log::info!("output {output} is blacklisted, waiting for a different output");
+    } else {
+        *recorder.lock().expect("recorder lock poisoned") =
+            Some(Recorder::spawn(&output, &target)?);
+    }
+
+    let mut events = SubSocket::new();
+    events.connect(&cli.publish).await?;
+    events.subscribe("").await?;
+
+    loop {
+        let message = events.recv().await?;
+        let topic = match message.get(0) {
+            Some(frame) => String::from_utf8(frame.to_vec())?,
+            None => continue,
+        };
+        let payload = match message.get(1) {
+            Some(frame) => String::from_utf8(frame.to_vec())?,
+            None => String::new(),
+        };
+
+        if parse_event(&topic, &payload).is_none() {
+            continue;
+        }
+
+        let (new_output, new_resolution) =
+            query_current(&cli.server, cli.timeout, cli.screen.as_deref()).await?;
+        if new_output == output && new_resolution == resolution {
+            continue;
+        }
+
+        log::info!("active output changed from {output} to {new_output}, restarting recorder");
+        if let Some(mut recorder) = recorder.lock().expect("recorder lock poisoned").take() {
+            recorder.kill();
+        }
+
+        if !not_output.iter().any(|o| o == &new_output) {
+            *recorder.lock().expect("recorder lock poisoned") =
+                Some(Recorder::spawn(&new_output, &target)?);
+        }
+
+        output = new_output;
+        resolution = new_resolution;
+    }
+}